@@ -0,0 +1,208 @@
+//! Online ingestion and incremental sync of the Central Council method collection.
+//!
+//! This is modelled on an incremental "remote settings" sync: a [`CcLibSync`] fetches the
+//! published CCCBR method collection from a URL, parses it into a [`MethodLib`], and caches the
+//! compact JSON form on disk (reusing [`MethodLib::to_json`]/[`MethodLib::from_json`]).  On every
+//! subsequent run the stored ETag is sent back as an `If-None-Match` header, so an unchanged
+//! dataset costs a single conditional request and the cached library is reused verbatim.  When the
+//! source *has* changed, the freshly-parsed entries are [merged](MethodLib::merge) into the cached
+//! library per-[`Stage`](crate::Stage) rather than rebuilding the whole map from scratch.
+//!
+//! This entire subsystem lives behind the `cc_lib_ingest` cargo feature so that the core crate
+//! stays dependency-light; enabling it also enables `method_lib_serde`, which provides the
+//! on-disk caching format.
+
+use std::{
+    fmt::{Display, Formatter},
+    fs,
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::MethodLib;
+
+/// Fetches and incrementally syncs a [`MethodLib`] from a remote CCCBR method collection.
+#[derive(Debug, Clone)]
+pub struct CcLibSync {
+    /// The URL from which the raw method collection is fetched.
+    source_url: String,
+    /// Where the compact JSON form of the library is cached on disk.
+    cache_path: PathBuf,
+}
+
+impl CcLibSync {
+    /// Creates a new `CcLibSync` which fetches from `source_url` and caches its result at
+    /// `cache_path`.
+    pub fn new(source_url: impl Into<String>, cache_path: impl Into<PathBuf>) -> Self {
+        Self {
+            source_url: source_url.into(),
+            cache_path: cache_path.into(),
+        }
+    }
+
+    /// Returns the path of the sidecar file which stores the sync metadata (the ETag) next to
+    /// the cached library.
+    fn meta_path(&self) -> PathBuf {
+        self.cache_path.with_extension("meta.json")
+    }
+
+    /// Syncs the library, returning the up-to-date [`MethodLib`].
+    ///
+    /// If a cached copy exists, its ETag is sent as a conditional request; a `304 Not Modified`
+    /// response short-circuits to the cached library.  Otherwise the remote collection is parsed
+    /// and merged into whatever was cached, and the new cache (plus its ETag) is written
+    /// back to disk.
+    pub fn sync(&self) -> Result<MethodLib, IngestError> {
+        let cached = self.load_cache()?;
+        let meta = self.load_meta()?;
+
+        let fetched = fetch(&self.source_url, meta.as_ref().and_then(|m| m.etag.as_deref()))?;
+        let Response { body, etag } = match fetched {
+            // The source hasn't changed since we last synced.
+            FetchOutcome::NotModified => match cached {
+                // The cache is still present, so we can reuse it verbatim.
+                Some(library) => return Ok(library),
+                // The ETag sidecar outlived its cache (e.g. the cache file was deleted by hand).
+                // A conditional request can only ever 304 in this state, so re-fetch
+                // unconditionally to rebuild the cache rather than wedging on an error.
+                None => match fetch(&self.source_url, None)? {
+                    FetchOutcome::Modified(response) => response,
+                    FetchOutcome::NotModified => {
+                        return Err(IngestError::SchemaMismatch(
+                            "remote returned 304 to an unconditional request".to_owned(),
+                        ))
+                    }
+                },
+            },
+            FetchOutcome::Modified(response) => response,
+        };
+
+        let parsed = super::parse_cc_lib::parse_cc_lib(&body).map_err(IngestError::parse)?;
+        let mut library = cached.unwrap_or_else(MethodLib::empty);
+        library.merge(parsed);
+
+        self.store_cache(&library)?;
+        self.store_meta(&CacheMeta { etag })?;
+
+        Ok(library)
+    }
+
+    /// Loads the cached library from disk, returning `None` if no cache has been written yet.
+    fn load_cache(&self) -> Result<Option<MethodLib>, IngestError> {
+        match fs::read_to_string(&self.cache_path) {
+            Ok(json) => MethodLib::from_json(&json)
+                .map(Some)
+                .map_err(IngestError::parse),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(IngestError::Io(e)),
+        }
+    }
+
+    /// Writes the compact JSON form of `library` to the cache path.
+    fn store_cache(&self, library: &MethodLib) -> Result<(), IngestError> {
+        let json = library.to_json().map_err(IngestError::parse)?;
+        write_atomic(&self.cache_path, json.as_bytes())
+    }
+
+    /// Loads the sync metadata sidecar, returning `None` if it's absent.
+    fn load_meta(&self) -> Result<Option<CacheMeta>, IngestError> {
+        match fs::read_to_string(self.meta_path()) {
+            Ok(json) => serde_json::from_str(&json)
+                .map(Some)
+                .map_err(|e| IngestError::SchemaMismatch(e.to_string())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(IngestError::Io(e)),
+        }
+    }
+
+    /// Writes the sync metadata sidecar.
+    fn store_meta(&self, meta: &CacheMeta) -> Result<(), IngestError> {
+        let json = serde_json::to_string(meta)
+            .map_err(|e| IngestError::SchemaMismatch(e.to_string()))?;
+        write_atomic(&self.meta_path(), json.as_bytes())
+    }
+}
+
+/// The metadata stored alongside a cached library, used to drive incremental syncs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CacheMeta {
+    /// The ETag returned with the last successfully-ingested dataset, if the server provided one.
+    etag: Option<String>,
+}
+
+/// The parsed outcome of a conditional fetch.
+enum FetchOutcome {
+    /// The server reported `304 Not Modified`; the cached copy is still current.
+    NotModified,
+    /// The server returned a fresh body.
+    Modified(Response),
+}
+
+/// A successful fetch which returned a fresh body.
+struct Response {
+    body: String,
+    etag: Option<String>,
+}
+
+/// Performs a conditional `GET` against `url`, passing `etag` as an `If-None-Match` header if
+/// present.
+fn fetch(url: &str, etag: Option<&str>) -> Result<FetchOutcome, IngestError> {
+    let mut request = ureq::get(url);
+    if let Some(etag) = etag {
+        request = request.set("If-None-Match", etag);
+    }
+    match request.call() {
+        // 3xx statuses other than redirects come back as `Ok`, so a 304 is detected here
+        Ok(response) if response.status() == 304 => Ok(FetchOutcome::NotModified),
+        Ok(response) => {
+            let etag = response.header("ETag").map(str::to_owned);
+            let body = response.into_string().map_err(IngestError::Network)?;
+            Ok(FetchOutcome::Modified(Response { body, etag }))
+        }
+        Err(ureq::Error::Status(304, _)) => Ok(FetchOutcome::NotModified),
+        Err(e) => Err(IngestError::Network(io::Error::new(io::ErrorKind::Other, e))),
+    }
+}
+
+/// Writes `bytes` to `path` via a temporary file and a rename, so a crash mid-write can't leave a
+/// half-written cache behind.
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), IngestError> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, bytes).map_err(IngestError::Io)?;
+    fs::rename(&tmp, path).map_err(IngestError::Io)
+}
+
+/// The ways in which ingesting a remote method library can fail.
+#[derive(Debug)]
+pub enum IngestError {
+    /// The dataset couldn't be fetched from the remote source.
+    Network(io::Error),
+    /// The fetched dataset couldn't be parsed into a [`MethodLib`].
+    Parse(String),
+    /// A cache or metadata file didn't match the expected schema.
+    SchemaMismatch(String),
+    /// Reading from or writing to the on-disk cache failed.
+    Io(io::Error),
+}
+
+impl IngestError {
+    /// Wraps any displayable parse error into [`IngestError::Parse`].
+    fn parse(error: impl Display) -> Self {
+        IngestError::Parse(error.to_string())
+    }
+}
+
+impl Display for IngestError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IngestError::Network(e) => write!(f, "failed to fetch method library: {}", e),
+            IngestError::Parse(e) => write!(f, "failed to parse method library: {}", e),
+            IngestError::SchemaMismatch(e) => write!(f, "method library schema mismatch: {}", e),
+            IngestError::Io(e) => write!(f, "method library cache I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for IngestError {}
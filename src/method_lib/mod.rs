@@ -1,4 +1,7 @@
-use std::{cmp::Ordering, collections::HashMap};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+};
 
 use edit_distance::edit_distance;
 use itertools::Itertools;
@@ -6,6 +9,8 @@ use shortlist::Shortlist;
 
 use crate::{method::FullClass, place_not::PnBlockParseError, Method, PnBlock, Stage};
 
+#[cfg(feature = "cc_lib_ingest")]
+pub mod ingest;
 mod lib_serde;
 pub(crate) mod parse_cc_lib;
 
@@ -13,12 +18,55 @@ pub(crate) mod parse_cc_lib;
 type LibraryMap = HashMap<Stage, HashMap<String, CompactMethod>>;
 
 /// A library of [`Methods`], usually that provided by the Central Council.
+///
+/// Alongside the raw `method_map`, a `MethodLib` keeps a few derived indices which are built once
+/// when it's constructed (and rebuilt on [`MethodLib::merge`]) so that interactive queries don't
+/// have to re-walk the whole library on every call:
+/// - `sorted` holds every title in iteration order (by [`Stage`], then alphabetically), backing
+///   [`MethodLib::iter`] and [`MethodLib::complete_prefix`];
+/// - `name_index` holds a per-[`Stage`] [`BkTree`] over the *name* portion of each title, and
+///   `title_index` a single [`BkTree`] over whole titles - both feeding
+///   [`MethodLib::generate_suggestions`].
 #[derive(Debug, Clone)]
 pub struct MethodLib {
     method_map: LibraryMap,
+    /// Every title, grouped by [`Stage`] (ordered) and sorted alphabetically within each stage.
+    sorted: BTreeMap<Stage, Vec<String>>,
+    /// A [`BkTree`] per [`Stage`], keyed by the lower-cased name portion of each title, with the
+    /// full title as its payload.  Used for stage-scoped suggestions.
+    name_index: HashMap<Stage, BkTree<String>>,
+    /// A single [`BkTree`] keyed by lower-cased whole titles, with `(Stage, title)` as its
+    /// payload.  Used as the fallback when a query's stage can't be resolved.
+    title_index: BkTree<(Stage, String)>,
 }
 
 impl MethodLib {
+    /// Creates a `MethodLib` from a raw `method_map`, building the derived suggestion and
+    /// completion indices once up-front.
+    pub(crate) fn new(method_map: LibraryMap) -> Self {
+        let mut sorted: BTreeMap<Stage, Vec<String>> = BTreeMap::new();
+        let mut name_index: HashMap<Stage, BkTree<String>> = HashMap::new();
+        let mut title_index = BkTree::default();
+        for (&stage, methods) in &method_map {
+            let titles = sorted.entry(stage).or_default();
+            let name_tree = name_index.entry(stage).or_default();
+            for title in methods.keys() {
+                titles.push(title.clone());
+                name_tree.insert(split_stage_word(title).0.to_lowercase(), title.clone());
+                title_index.insert(title.to_lowercase(), (stage, title.clone()));
+            }
+        }
+        // Sort each stage's titles alphabetically; the `BTreeMap` already orders the stages.
+        for titles in sorted.values_mut() {
+            titles.sort_unstable();
+        }
+        Self {
+            method_map,
+            sorted,
+            name_index,
+            title_index,
+        }
+    }
     /// Searches this `MethodLib` for a [`Method`] with a title, returning the [`Method`] if found
     /// and `None` otherwise.  The failure state for this function is not very useful - if you want
     /// to provide useful suggestions for your user, then consider using
@@ -58,37 +106,132 @@ impl MethodLib {
 
     /// Searches this `MethodLib` for a [`Method`] with a title.  If this title is found in the
     /// library, then `Ok(Method)` is returned.  Otherwise, a list of similar titles are returned,
-    /// along with their [Levenstein edit
+    /// each paired with the [`Stage`] it belongs to and its case-insensitive [Levenstein edit
     /// distance](https://en.wikipedia.org/wiki/Levenshtein_distance) from the requested title.
-    /// These are sorted with the closest results first
+    /// When the title's stage word is recognised, this distance is measured between the name
+    /// portions only (excluding the stage word).  These are sorted with the closest results first
     pub fn get_by_title_with_suggestions<'s>(
         &'s self,
         title: &str,
         num_suggestions: usize,
-    ) -> QueryResult<Vec<(&'s str, usize)>> {
+    ) -> QueryResult<Vec<(Stage, &'s str, usize)>> {
         self.get_by_title(title)
             .map_not_found(|()| self.generate_suggestions(title, num_suggestions))
     }
 
-    /// Generate a list of method title suggestions based on the Levenstein edit from a given title
+    /// Searches this `MethodLib` for every [`Method`] which satisfies a set of structural and
+    /// musical predicates (a [`MethodQuery`]), rather than looking methods up by their exact
+    /// title.
+    ///
+    /// This runs as a two-phase probe (similar to how `rustc` probes candidates by name before
+    /// checking their suitability).  First, we cheaply gather candidate titles per [`Stage`] by
+    /// testing each [`CompactMethod`] against the predicates which don't require parsing place
+    /// notation (stage, classification and number of hunt bells, plus a verbatim place-notation
+    /// fragment).  Only the candidates which survive are then materialised into full [`Method`]s
+    /// by expanding them through [`CompactMethod::to_method`], so we avoid parsing the place
+    /// notation of methods which can't possibly match.  Predicates which need the parsed method -
+    /// currently the lead head - are applied in this second phase.
+    ///
+    /// The returned [`Vec`] mixes successful matches with [`SearchResult::PnParseErr`] markers for
+    /// candidates whose title matched but whose place notation failed to parse - mirroring
+    /// [`QueryResult::PnParseErr`].
+    pub fn search<'s>(&'s self, query: &MethodQuery) -> Vec<SearchResult<'s>> {
+        let mut results = Vec::new();
+        for (&stage, methods) in &self.method_map {
+            // Phase 1: cheaply reject whole stages, then individual `CompactMethod`s, before we
+            // commit to parsing any place notation.
+            if !query.stage_matches(stage) {
+                continue;
+            }
+            for (title, compact) in methods {
+                if !query.compact_matches(compact) {
+                    continue;
+                }
+                // Phase 2: only the candidates which passed the cheap filters are materialised,
+                // then tested against the predicates which need the parsed `Method`.
+                match compact.to_method(stage, title.clone()) {
+                    Ok(method) => {
+                        if query.method_matches(&method) {
+                            results.push(SearchResult::Success(method));
+                        }
+                    }
+                    Err((pn, error)) => results.push(SearchResult::PnParseErr { title, pn, error }),
+                }
+            }
+        }
+        results
+    }
+
+    /// Iterates over every method stored in this library in a deterministic order: by [`Stage`]
+    /// first, then by title alphabetically within each stage.  This imposes a stable ordering on
+    /// top of the nested [`HashMap`]s, which otherwise iterate arbitrarily.
+    ///
+    /// Each item is the lightweight `(Stage, title)` pair; callers can lazily upgrade a chosen
+    /// title to a full [`Method`] via [`MethodLib::get_by_title`].
+    pub fn iter(&self) -> impl Iterator<Item = (Stage, &str)> {
+        // `sorted` is already in iteration order (the `BTreeMap` orders the stages and each
+        // `Vec` was sorted at construction), so we can just walk it.
+        self.sorted
+            .iter()
+            .flat_map(|(&stage, titles)| titles.iter().map(move |title| (stage, title.as_str())))
+    }
+
+    /// Returns up to `limit` titles which begin with `prefix`, for type-ahead completion.  The
+    /// comparison is case-insensitive, and the results are returned in the same deterministic
+    /// order as [`MethodLib::iter`] (by [`Stage`], then title).  Pass `Some(stage)` to scope the
+    /// completion to a single [`Stage`].
+    ///
+    /// This walks the sorted index built at construction time; when a `stage` is given only that
+    /// stage's submap is scanned rather than the whole library.
+    ///
+    /// Like [`MethodLib::generate_suggestions`], the returned titles are lightweight borrows which
+    /// callers can lazily upgrade to a full [`Method`] via [`MethodLib::get_by_title`].
+    pub fn complete_prefix<'s>(
+        &'s self,
+        prefix: &str,
+        limit: usize,
+        stage: Option<Stage>,
+    ) -> Vec<(Stage, &'s str)> {
+        let prefix = prefix.to_lowercase();
+        let mut results = Vec::new();
+        match stage {
+            // Scoped to one stage: scan only that stage's sorted submap.
+            Some(stage) => {
+                if let Some(titles) = self.sorted.get(&stage) {
+                    collect_prefix_matches(stage, titles, &prefix, limit, &mut results);
+                }
+            }
+            // Unscoped: walk every stage in iteration order until we've filled the shortlist.
+            None => {
+                for (&stage, titles) in &self.sorted {
+                    collect_prefix_matches(stage, titles, &prefix, limit, &mut results);
+                    if results.len() >= limit {
+                        break;
+                    }
+                }
+            }
+        }
+        results
+    }
+
+    /// Generate a list of method title suggestions based on the Levenstein edit distance from a
+    /// given title.
+    ///
+    /// Suggestions are stage-aware.  The trailing word of `title` is treated as a stage name: if
+    /// it's a valid stage - or close enough to one of the library's stages to be corrected, e.g.
+    /// "Majr" -> "Major" - then we search only that [`Stage`]'s submap and compare just the name
+    /// portion of each title, so that the (correct) stage word doesn't dilute the edit distance.
+    /// If the trailing word resembles no known stage, we fall back to comparing the whole title
+    /// against every stage.  Each suggestion is returned alongside the [`Stage`] it came from, so
+    /// that callers can group results.
     fn generate_suggestions<'lib>(
         &'lib self,
         title: &str,
         num_suggestions: usize,
-    ) -> Vec<(&'lib str, usize)> {
+    ) -> Vec<(Stage, &'lib str, usize)> {
         /// A new-type over the suggestions, which is ordered by the edit distance
         #[derive(Debug, Clone, Copy)]
-        // Forcing `repr` transparent will make sure that the memory layout is identical to `(&str,
-        // usize)` which will usually let LLVM optimise away the allocation in
-        // `Shortlist::into_sorted_vec`
-        #[repr(transparent)]
-        struct Suggestion<'s>((&'s str, usize));
-
-        impl<'s> Suggestion<'s> {
-            fn new(actual: &str, suggestion: &'s str) -> Self {
-                Suggestion((suggestion, edit_distance(actual, suggestion)))
-            }
-        }
+        struct Suggestion<'s>(Stage, &'s str, usize);
 
         impl<'s> PartialOrd for Suggestion<'s> {
             fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
@@ -100,26 +243,55 @@ impl MethodLib {
             fn cmp(&self, other: &Self) -> Ordering {
                 // Make sure to sort them in reverse order, because the best suggestions have the
                 // smallest edit distance
-                self.0 .1.cmp(&other.0 .1).reverse()
+                self.2.cmp(&other.2).reverse()
             }
         }
 
         impl<'s> PartialEq for Suggestion<'s> {
             fn eq(&self, other: &Self) -> bool {
-                self.0 .1 == other.0 .1
+                self.2 == other.2
             }
         }
 
         impl<'s> Eq for Suggestion<'s> {}
 
-        // Test each method as a suggestion, pushing the suggestions into a shortlist
+        // Cap the search radius `rustc`-style so that we never return wildly different titles.
+        let radius_of = |query: &str| query.chars().count().max(1) / 3 + 1;
+
+        // Walk the radius-bounded neighbourhood of the query through the pre-built BK-tree(s),
+        // feeding every match into the shortlist.  The indices are built once when the library is
+        // constructed, so a query only pays for the bounded traversal - not for rebuilding a tree.
         let mut suggestion_shortlist = Shortlist::new(num_suggestions);
-        for methods in self.method_map.values() {
-            suggestion_shortlist.append(
-                methods
-                    .keys()
-                    .map(|stored_title| Suggestion::new(title, stored_title)),
-            );
+        match self.resolve_stage(split_stage_word(title).1) {
+            // We know the stage, so use its name-portion index and compare against the name
+            // portion of the query.
+            Some(stage) => {
+                if let Some(tree) = self.name_index.get(&stage) {
+                    let query = split_stage_word(title).0.to_lowercase();
+                    tree.for_each_within(&query, radius_of(&query), |stored_title, distance| {
+                        suggestion_shortlist.append(std::iter::once(Suggestion(
+                            stage,
+                            stored_title.as_str(),
+                            distance,
+                        )));
+                    });
+                }
+            }
+            // No recognisable stage, so fall back to the whole-title index across every stage.
+            None => {
+                let query = title.to_lowercase();
+                self.title_index.for_each_within(
+                    &query,
+                    radius_of(&query),
+                    |&(stage, ref stored_title), distance| {
+                        suggestion_shortlist.append(std::iter::once(Suggestion(
+                            stage,
+                            stored_title.as_str(),
+                            distance,
+                        )));
+                    },
+                );
+            }
         }
 
         let mut best_suggestions = suggestion_shortlist.into_sorted_vec();
@@ -127,9 +299,155 @@ impl MethodLib {
         best_suggestions.reverse();
         best_suggestions
             .into_iter()
-            .map(|Suggestion(vs)| vs)
+            .map(|Suggestion(stage, stored_title, distance)| (stage, stored_title, distance))
             .collect_vec()
     }
+
+    /// Resolves the trailing word of a queried title to a [`Stage`] which is present in this
+    /// library, returning `None` if it resembles no known stage.
+    ///
+    /// An exact (case-insensitive) stage name is used directly; otherwise we correct a misspelled
+    /// stage word (e.g. "Royl" -> "Royal") against the stage words actually present in the
+    /// library, accepting the closest one within a small edit-distance radius.
+    fn resolve_stage(&self, stage_word: &str) -> Option<Stage> {
+        let lower = stage_word.to_lowercase();
+        // An exact stage name which is actually present in the library wins outright.
+        if let Some(stage) = Stage::from_lower_case_name(&lower) {
+            if self.method_map.contains_key(&stage) {
+                return Some(stage);
+            }
+        }
+        // Otherwise, try to correct the (possibly misspelled) stage word.
+        let radius = lower.chars().count().max(1) / 3 + 1;
+        self.method_map
+            .iter()
+            .filter_map(|(&stage, methods)| {
+                let canonical = split_stage_word(methods.keys().next()?).1.to_lowercase();
+                let distance = edit_distance(&canonical, &lower);
+                (distance <= radius).then_some((stage, distance))
+            })
+            .min_by_key(|&(_, distance)| distance)
+            .map(|(stage, _)| stage)
+    }
+}
+
+/// Splits a method title into its name portion and its trailing stage word, e.g.
+/// `"Cambridge Surprise Major"` into `("Cambridge Surprise", "Major")`.  If the title has no
+/// spaces, the whole title is treated as the stage word.
+fn split_stage_word(title: &str) -> (&str, &str) {
+    match title.rsplit_once(' ') {
+        Some((name, stage_word)) => (name, stage_word),
+        None => ("", title),
+    }
+}
+
+/// Appends every title in `titles` (case-insensitively) beginning with the already-lower-cased
+/// `prefix` to `results`, stopping once `results` holds `limit` entries.
+fn collect_prefix_matches<'s>(
+    stage: Stage,
+    titles: &'s [String],
+    prefix: &str,
+    limit: usize,
+    results: &mut Vec<(Stage, &'s str)>,
+) {
+    for title in titles {
+        if results.len() >= limit {
+            break;
+        }
+        if title.to_lowercase().starts_with(prefix) {
+            results.push((stage, title.as_str()));
+        }
+    }
+}
+
+/// A [BK-tree](https://en.wikipedia.org/wiki/BK-tree), used by
+/// [`MethodLib::generate_suggestions`] to find close matches without comparing the query against
+/// every stored key.  Each entry is a lower-cased `key` (against which edit distances are
+/// measured, so capitalisation differences don't inflate the distance) paired with an arbitrary
+/// `item` which is handed back to callers.
+///
+/// A BK-tree exploits the triangle inequality of the edit distance: each node stores a key and
+/// indexes its children by their integer edit distance from it.  To find every entry within a
+/// radius `r` of a query `t`, we compute `d = dist(node, t)`, keep the node if `d <= r`, and then
+/// only recurse into children whose edge key lies in `[d - r, d + r]` - pruning the rest of the
+/// tree.
+#[derive(Debug, Clone)]
+struct BkTree<T> {
+    root: Option<BkNode<T>>,
+}
+
+impl<T> Default for BkTree<T> {
+    fn default() -> Self {
+        Self { root: None }
+    }
+}
+
+impl<T> BkTree<T> {
+    /// Inserts an entry into the tree, keyed by the (already lower-cased) `key`.
+    fn insert(&mut self, key: String, item: T) {
+        let node = BkNode::new(key, item);
+        match &mut self.root {
+            Some(root) => root.insert(node),
+            None => self.root = Some(node),
+        }
+    }
+
+    /// Calls `f` with `(&item, distance)` for every entry whose edit distance from the already
+    /// lower-cased `query` is at most `radius`.
+    fn for_each_within<'a>(&'a self, query: &str, radius: usize, mut f: impl FnMut(&'a T, usize)) {
+        if let Some(root) = &self.root {
+            root.for_each_within(query, radius, &mut f);
+        }
+    }
+}
+
+/// A single node of a [`BkTree`].
+#[derive(Debug, Clone)]
+struct BkNode<T> {
+    /// The lower-cased key, against which edit distances are measured.
+    key: String,
+    /// The item handed back to callers when this node matches.
+    item: T,
+    /// Children indexed by their integer edit distance from this node.
+    children: HashMap<usize, BkNode<T>>,
+}
+
+impl<T> BkNode<T> {
+    fn new(key: String, item: T) -> Self {
+        Self {
+            key,
+            item,
+            children: HashMap::new(),
+        }
+    }
+
+    /// Inserts `new` somewhere in the subtree rooted at `self`.
+    fn insert(&mut self, new: BkNode<T>) {
+        let distance = edit_distance(&self.key, &new.key);
+        match self.children.get_mut(&distance) {
+            Some(child) => child.insert(new),
+            None => {
+                self.children.insert(distance, new);
+            }
+        }
+    }
+
+    /// The recursive half of [`BkTree::for_each_within`].
+    fn for_each_within<'a>(&'a self, query: &str, radius: usize, f: &mut impl FnMut(&'a T, usize)) {
+        let distance = edit_distance(&self.key, query);
+        if distance <= radius {
+            f(&self.item, distance);
+        }
+        // Only the children whose edge key lies within `radius` of `distance` can possibly hold a
+        // match, so we prune everything else by the triangle inequality.
+        let min = distance.saturating_sub(radius);
+        let max = distance + radius;
+        for (&key, child) in &self.children {
+            if (min..=max).contains(&key) {
+                child.for_each_within(query, radius, f);
+            }
+        }
+    }
 }
 
 /// (De)serialising libraries to and from JSON
@@ -147,6 +465,32 @@ impl MethodLib {
     }
 }
 
+/// Incremental merging, used when syncing a library from a remote source.
+#[cfg(feature = "cc_lib_ingest")]
+impl MethodLib {
+    /// Creates an empty `MethodLib`, used as the starting point for an incremental sync when no
+    /// cache exists yet.
+    pub(crate) fn empty() -> Self {
+        Self::new(LibraryMap::new())
+    }
+
+    /// Merges the freshly-parsed `other` into `self`, then rebuilds the derived indices once over
+    /// the merged map.
+    ///
+    /// Because a sync re-parses the upstream dataset in full, `other` is authoritative for every
+    /// [`Stage`] it contains: each such submap in `method_map` is *replaced* wholesale rather than
+    /// extended, so methods removed or renamed upstream don't linger in the cache.  (A stage which
+    /// disappears from the dataset entirely is the one case left untouched; reconciling that - and
+    /// a finer-grained diff which only re-parses changed entries - is left for a follow-up.)
+    pub(crate) fn merge(&mut self, other: MethodLib) {
+        let mut method_map = std::mem::take(&mut self.method_map);
+        for (stage, methods) in other.method_map {
+            method_map.insert(stage, methods);
+        }
+        *self = MethodLib::new(method_map);
+    }
+}
+
 /// A light-weight version of [`Method`] that can be easily stored in a method library.  This is
 /// not intended to be used outside of [`MethodLib`]
 #[derive(Debug, Clone)]
@@ -169,6 +513,87 @@ impl CompactMethod {
     }
 }
 
+/// A set of structural and musical predicates used to [search](MethodLib::search) a
+/// [`MethodLib`].  Every field which is left as `None` is treated as "don't care"; a [`Method`]
+/// matches the query only if it satisfies all the fields which are set.
+///
+/// Most predicates are cheap enough to test in phase one against the stored [`CompactMethod`];
+/// `lead_head_code` is the exception, as it depends on the method's lead head and so can only be
+/// tested once the [`Method`] has been materialised in phase two.  (The palindrome and
+/// Plain-Bob-lead-end predicates hinted at by the request remain a follow-up.)
+#[derive(Debug, Clone, Default)]
+pub struct MethodQuery {
+    /// If set, only methods whose place notation contains this fragment verbatim are matched.
+    pub place_notation_fragment: Option<String>,
+    /// If set, only methods with exactly this [`FullClass`] are matched.
+    pub full_class: Option<FullClass>,
+    /// If set, only methods whose [`Stage`] lies within this inclusive range are matched.
+    pub stage_range: Option<(Stage, Stage)>,
+    /// If set, only methods with this many hunt bells are matched.
+    pub num_hunt_bells: Option<u8>,
+    /// If set, only methods whose lead head, written out as a row (e.g. `"13527486"`), equals this
+    /// string are matched.  Lead head codes such as `"a"` or `"m"` are not recognised.  Tested
+    /// against the materialised [`Method`] in phase two of [`MethodLib::search`].
+    pub lead_head_code: Option<String>,
+}
+
+impl MethodQuery {
+    /// Returns `true` if a [`Stage`] is permitted by this query's stage predicate.
+    fn stage_matches(&self, stage: Stage) -> bool {
+        match self.stage_range {
+            Some((min, max)) => min <= stage && stage <= max,
+            None => true,
+        }
+    }
+
+    /// Returns `true` if a [`CompactMethod`] satisfies every predicate which can be tested without
+    /// parsing its place notation.
+    fn compact_matches(&self, compact: &CompactMethod) -> bool {
+        if let Some(full_class) = self.full_class {
+            if compact.full_class != full_class {
+                return false;
+            }
+        }
+        if let Some(num_hunt_bells) = self.num_hunt_bells {
+            if compact.full_class.num_hunt_bells() != num_hunt_bells {
+                return false;
+            }
+        }
+        if let Some(fragment) = &self.place_notation_fragment {
+            if !compact.place_notation.contains(fragment.as_str()) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Returns `true` if a materialised [`Method`] satisfies the predicates which can only be
+    /// tested once its place notation has been parsed.
+    fn method_matches(&self, method: &Method) -> bool {
+        if let Some(lead_head_code) = &self.lead_head_code {
+            if &method.lead_head().to_string() != lead_head_code {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// The outcome of materialising a single candidate title during a [search](MethodLib::search).
+#[derive(Debug, Clone)]
+#[must_use]
+pub enum SearchResult<'lib> {
+    /// The candidate matched every predicate and its place notation parsed successfully.
+    Success(Method),
+    /// The candidate's title matched, but its place notation failed to parse (mirrors
+    /// [`QueryResult::PnParseErr`]).
+    PnParseErr {
+        title: &'lib str,
+        pn: &'lib str,
+        error: PnBlockParseError,
+    },
+}
+
 #[derive(Debug, Clone)]
 #[must_use]
 pub enum QueryResult<'lib, T> {
@@ -209,4 +634,158 @@ impl<'lib, T> QueryResult<'lib, T> {
             QueryResult::NotFound(v) => QueryResult::NotFound(f(v)),
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::method::FullClass;
+
+    /// Builds a [`CompactMethod`] with a placeholder class, for tests which only exercise the
+    /// title/stage machinery and never materialise the place notation.
+    fn compact(title: &str, place_notation: &str) -> CompactMethod {
+        CompactMethod {
+            name: title.to_owned(),
+            full_class: FullClass::default(),
+            place_notation: place_notation.to_owned(),
+        }
+    }
+
+    /// Builds a [`MethodLib`] from `(stage, title, place_notation)` triples.
+    fn lib(entries: &[(Stage, &str, &str)]) -> MethodLib {
+        let mut method_map: LibraryMap = HashMap::new();
+        for &(stage, title, pn) in entries {
+            method_map
+                .entry(stage)
+                .or_default()
+                .insert(title.to_owned(), compact(title, pn));
+        }
+        MethodLib::new(method_map)
+    }
+
+    #[test]
+    fn split_stage_word_works() {
+        assert_eq!(
+            split_stage_word("Cambridge Surprise Major"),
+            ("Cambridge Surprise", "Major")
+        );
+        assert_eq!(split_stage_word("Grandsire Triples"), ("Grandsire", "Triples"));
+        assert_eq!(split_stage_word("Major"), ("", "Major"));
+    }
+
+    #[test]
+    fn bk_tree_traversal_matches_brute_force() {
+        let words = [
+            "Cambridge",
+            "Bristol",
+            "London",
+            "Yorkshire",
+            "Lincolnshire",
+            "Superlative",
+            "Rutland",
+            "Pudsey",
+        ];
+        let mut tree = BkTree::default();
+        for &w in &words {
+            tree.insert(w.to_lowercase(), w.to_owned());
+        }
+        for &query in &["cambridge", "bristl", "yorkshir", "xyz", "londonn", ""] {
+            for radius in 0..=4 {
+                // What the radius-bounded traversal finds...
+                let mut from_tree = Vec::new();
+                tree.for_each_within(query, radius, |item: &String, d| {
+                    from_tree.push((item.clone(), d))
+                });
+                from_tree.sort();
+                // ...must match a brute-force scan over every word.
+                let mut expected: Vec<(String, usize)> = words
+                    .iter()
+                    .filter_map(|w| {
+                        let d = edit_distance(&w.to_lowercase(), query);
+                        (d <= radius).then_some((w.to_string(), d))
+                    })
+                    .collect();
+                expected.sort();
+                assert_eq!(from_tree, expected, "query={query:?} radius={radius}");
+            }
+        }
+    }
+
+    #[test]
+    fn suggestions_are_stage_aware() {
+        let lib = lib(&[
+            (Stage::MAJOR, "Cambridge Surprise Major", "x"),
+            (Stage::MAJOR, "Yorkshire Surprise Major", "x"),
+            (Stage::ROYAL, "Cambridge Surprise Royal", "x"),
+        ]);
+        // A misspelled stage word ("Majr") is corrected to Major, and only the name portion is
+        // compared so the typo'd name still matches.
+        let suggestions = match lib.get_by_title_with_suggestions("Cambirdge Surprise Majr", 5) {
+            QueryResult::NotFound(suggestions) => suggestions,
+            _ => panic!("expected the title to be missing"),
+        };
+        assert!(suggestions
+            .iter()
+            .any(|&(stage, title, _)| stage == Stage::MAJOR && title == "Cambridge Surprise Major"));
+        // The Royal method must not leak in, because the stage resolved to Major.
+        assert!(suggestions.iter().all(|&(stage, _, _)| stage == Stage::MAJOR));
+    }
+
+    #[test]
+    fn iter_is_ordered_and_completion_is_scoped() {
+        let lib = lib(&[
+            (Stage::MAJOR, "Yorkshire Surprise Major", "x"),
+            (Stage::MAJOR, "Cambridge Surprise Major", "x"),
+            (Stage::MINOR, "Cambridge Surprise Minor", "x"),
+        ]);
+        // `iter` orders by stage (Minor before Major), then alphabetically within a stage.
+        assert_eq!(
+            lib.iter().collect::<Vec<_>>(),
+            vec![
+                (Stage::MINOR, "Cambridge Surprise Minor"),
+                (Stage::MAJOR, "Cambridge Surprise Major"),
+                (Stage::MAJOR, "Yorkshire Surprise Major"),
+            ]
+        );
+        // `complete_prefix` is case-insensitive and can be scoped to a single stage.
+        assert_eq!(
+            lib.complete_prefix("cam", 10, Some(Stage::MAJOR)),
+            vec![(Stage::MAJOR, "Cambridge Surprise Major")]
+        );
+    }
+
+    #[test]
+    fn search_filters_by_stage_and_surfaces_parse_errors() {
+        let lib = lib(&[(Stage::MAJOR, "Dud Major", "not-valid-pn")]);
+        // A stage range which excludes the only method materialises nothing.
+        let query = MethodQuery {
+            stage_range: Some((Stage::MINOR, Stage::MINOR)),
+            ..Default::default()
+        };
+        assert!(lib.search(&query).is_empty());
+        // A matching candidate whose place notation won't parse is surfaced as a `PnParseErr`.
+        let query = MethodQuery {
+            stage_range: Some((Stage::MAJOR, Stage::MAJOR)),
+            place_notation_fragment: Some("not-valid".to_owned()),
+            ..Default::default()
+        };
+        let results = lib.search(&query);
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0], SearchResult::PnParseErr { .. }));
+    }
+
+    #[test]
+    fn search_filters_by_lead_head_code() {
+        // A method with parseable place notation, so phase two can materialise it.
+        let lib = lib(&[(Stage::MAJOR, "Plain Bob Major", "x18x18x18x18,12")]);
+        // A lead head code which can't possibly match filters the method out in phase two.
+        let query = MethodQuery {
+            lead_head_code: Some("not-a-real-lead-head".to_owned()),
+            ..Default::default()
+        };
+        assert!(lib.search(&query).is_empty());
+        // With no lead-head predicate the same method is returned.
+        let results = lib.search(&MethodQuery::default());
+        assert!(matches!(results.as_slice(), [SearchResult::Success(_)]));
+    }
+}